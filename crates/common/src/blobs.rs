@@ -13,18 +13,78 @@
 // limitations under the License.
 
 use crate::client::log;
-use crate::rkyv::kzg::{BlobDef, Bytes48Def};
+use crate::rkyv::kzg::{BlobDef, Bytes48Def, CellDef};
 use alloy_eips::eip4844::{
     kzg_to_versioned_hash, Blob, IndexedBlobHash, BLS_MODULUS, FIELD_ELEMENTS_PER_BLOB,
 };
-use alloy_primitives::{B256, U256};
+use alloy_primitives::{keccak256, Bytes, B256, U256};
 use alloy_rpc_types_beacon::sidecar::BlobData;
 use async_trait::async_trait;
-use c_kzg::{ethereum_kzg_settings, Bytes48};
+use c_kzg::{ethereum_kzg_settings, Bytes48, Cell, KzgSettings};
 use kona_derive::errors::BlobProviderError;
 use kona_derive::traits::BlobProvider;
 use kona_protocol::BlockInfo;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// EIP-7594 (PeerDAS) parameters: a blob's `FIELD_ELEMENTS_PER_BLOB` elements are erasure-extended
+/// and split into this many cells, each holding `FIELD_ELEMENTS_PER_CELL` field elements.
+pub const CELLS_PER_EXT_BLOB: usize = 128;
+pub const FIELD_ELEMENTS_PER_CELL: usize = 64;
+const BYTES_PER_CELL: usize = FIELD_ELEMENTS_PER_CELL * 32;
+/// A blob can be fully reconstructed from any half (the erasure-coding rate) of its cells.
+const MIN_RECOVERABLE_CELLS: usize = CELLS_PER_EXT_BLOB / 2;
+
+/// The KZG trusted setup and precompute level used to verify blob/cell proofs. Resolving a
+/// custom setup (e.g. for a devnet/minimal preset, or a higher precompute for faster batch
+/// verification) is comparatively expensive, so callers build one `KzgConfig` and share it
+/// across every [PreloadedBlobProvider]/[BlobCellWitness] it verifies.
+#[derive(Clone)]
+pub enum KzgConfig {
+    /// The bundled mainnet trusted setup, precomputing tables up to the given level (higher
+    /// values trade memory for faster batch verification; `0` precomputes nothing).
+    Mainnet(u64),
+    /// A trusted setup loaded from raw `g1`/`g2` Lagrange point bytes, with its own precompute
+    /// level.
+    Custom(Arc<KzgSettings>),
+}
+
+impl KzgConfig {
+    /// Loads a custom trusted setup from raw `g1`/`g2` Lagrange point bytes, precomputing tables
+    /// up to `precompute` (higher values trade memory for faster batch verification).
+    pub fn from_trusted_setup(
+        g1: &[u8],
+        g2: &[u8],
+        precompute: u64,
+    ) -> Result<Self, BlobProviderError> {
+        let settings = KzgSettings::load_trusted_setup(g1, g2, precompute)
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+        Ok(Self::Custom(Arc::new(settings)))
+    }
+
+    fn settings(&self) -> &KzgSettings {
+        match self {
+            Self::Mainnet(precompute) => ethereum_kzg_settings(*precompute),
+            Self::Custom(settings) => settings,
+        }
+    }
+}
+
+impl Default for KzgConfig {
+    fn default() -> Self {
+        Self::Mainnet(0)
+    }
+}
+
+impl std::fmt::Debug for KzgConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Mainnet(precompute) => write!(f, "KzgConfig::Mainnet({precompute})"),
+            Self::Custom(_) => write!(f, "KzgConfig::Custom"),
+        }
+    }
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BlobFetchRequest {
@@ -42,36 +102,261 @@ pub struct BlobWitnessData {
     pub commitments: Vec<Bytes48>,
     #[rkyv(with = rkyv::with::Map<Bytes48Def>)]
     pub proofs: Vec<Bytes48>,
+    /// Blobs proven available via a sampled subset of their EIP-7594 cells instead of a
+    /// full-blob proof, kept separate from `blobs`/`commitments`/`proofs` above.
+    pub cell_witnesses: Vec<BlobCellWitness>,
+}
+
+/// A commitment plus a (possibly partial) set of its EIP-7594 cells and their cell proofs. At
+/// least [MIN_RECOVERABLE_CELLS] of the [CELLS_PER_EXT_BLOB] cells must be present for the
+/// underlying blob to be reconstructible.
+#[derive(
+    Clone, Debug, Default, Serialize, Deserialize, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize,
+)]
+pub struct BlobCellWitness {
+    #[rkyv(with = Bytes48Def)]
+    pub commitment: Bytes48,
+    pub cell_indices: Vec<u64>,
+    #[rkyv(with = rkyv::with::Map<CellDef>)]
+    pub cells: Vec<Cell>,
+    #[rkyv(with = rkyv::with::Map<Bytes48Def>)]
+    pub proofs: Vec<Bytes48>,
+}
+
+impl BlobCellWitness {
+    /// Computes all [CELLS_PER_EXT_BLOB] cells and cell proofs for `blob`, keeping only the
+    /// cells at `sample_indices` so a prover can commit to and later prove availability from a
+    /// sampled subset rather than shipping the whole blob.
+    pub fn sampled(
+        kzg: &KzgConfig,
+        commitment: Bytes48,
+        blob: &Blob,
+        sample_indices: &[u64],
+    ) -> Result<Self, BlobProviderError> {
+        let (all_cells, all_proofs) = kzg
+            .settings()
+            .compute_cells_and_kzg_proofs(&c_kzg::Blob::new(blob.0))
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+        let mut cells = Vec::with_capacity(sample_indices.len());
+        let mut proofs = Vec::with_capacity(sample_indices.len());
+        for &index in sample_indices {
+            let i = index as usize;
+            cells.push(
+                *all_cells
+                    .get(i)
+                    .ok_or_else(|| BlobProviderError::Backend(format!("cell index {index} out of range")))?,
+            );
+            proofs.push(
+                *all_proofs
+                    .get(i)
+                    .ok_or_else(|| BlobProviderError::Backend(format!("cell index {index} out of range")))?,
+            );
+        }
+        Ok(Self {
+            commitment,
+            cell_indices: sample_indices.to_vec(),
+            cells,
+            proofs,
+        })
+    }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct PreloadedBlobProvider {
-    entries: Vec<(B256, Blob)>,
+    /// The trusted setup `entries` were verified against, kept around rather than re-resolved.
+    kzg: KzgConfig,
+    /// `(block_ref, blob_index, versioned_hash, blob)`, one entry per requested blob.
+    entries: Vec<(BlockInfo, u64, B256, Blob)>,
 }
 
-impl From<BlobWitnessData> for PreloadedBlobProvider {
-    fn from(value: BlobWitnessData) -> Self {
-        let blobs = value
+impl PreloadedBlobProvider {
+    /// Verifies `witness` against `kzg` and binds every blob it carries to the `expected` fetch
+    /// requests that were actually issued during derivation, so a malicious witness cannot
+    /// substitute a differently-hashed (but internally valid) blob.
+    pub fn new(
+        kzg: KzgConfig,
+        witness: BlobWitnessData,
+        expected: &[BlobFetchRequest],
+    ) -> Result<Self, BlobProviderError> {
+        let blobs = witness
             .blobs
             .into_iter()
             .map(|b| c_kzg::Blob::new(b.0))
             .collect::<Vec<_>>();
-        ethereum_kzg_settings(0)
+        let valid = kzg
+            .settings()
             .verify_blob_kzg_proof_batch(
                 blobs.as_slice(),
-                value.commitments.as_slice(),
-                value.proofs.as_slice(),
+                witness.commitments.as_slice(),
+                witness.proofs.as_slice(),
             )
-            .expect("Failed to batch validate kzg proofs");
-        let hashes = value
-            .commitments
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+        if !valid {
+            return Err(BlobProviderError::Backend(
+                "witness blob kzg proofs failed batch verification".to_string(),
+            ));
+        }
+
+        let mut witnessed = core::iter::zip(
+            witness
+                .commitments
+                .iter()
+                .map(|c| kzg_to_versioned_hash(c.as_slice())),
+            blobs.into_iter().map(|b| Blob::from(*b)),
+        )
+        .collect::<Vec<_>>();
+
+        witnessed.extend(Self::verify_cell_witnesses(&kzg, &witness.cell_witnesses)?);
+
+        if witnessed.len() != expected.len() {
+            return Err(BlobProviderError::Backend(format!(
+                "witness carries {} blob(s) but {} were requested",
+                witnessed.len(),
+                expected.len()
+            )));
+        }
+
+        let mut entries = Vec::with_capacity(expected.len());
+        for request in expected {
+            let position = witnessed
+                .iter()
+                .position(|(hash, _)| *hash == request.blob_hash.hash)
+                .ok_or_else(|| {
+                    BlobProviderError::Backend(format!(
+                        "witness is missing requested blob hash {}",
+                        request.blob_hash.hash
+                    ))
+                })?;
+            let (hash, blob) = witnessed.remove(position);
+            entries.push((request.block_ref, request.blob_hash.index, hash, blob));
+        }
+
+        Ok(Self { kzg, entries })
+    }
+
+    /// Batch-verifies every cell witness's cell proofs, reconstructing each underlying blob from
+    /// its cells (recovering the missing ones when necessary), and returns them keyed by
+    /// versioned hash so they can be merged into the classic full-blob witness set.
+    fn verify_cell_witnesses(
+        kzg: &KzgConfig,
+        cell_witnesses: &[BlobCellWitness],
+    ) -> Result<Vec<(B256, Blob)>, BlobProviderError> {
+        if cell_witnesses.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let commitments = cell_witnesses
             .iter()
-            .map(|c| kzg_to_versioned_hash(c.as_slice()))
+            .flat_map(|w| core::iter::repeat(w.commitment).take(w.cells.len()))
             .collect::<Vec<_>>();
-        let entries = core::iter::zip(hashes, blobs.into_iter().map(|b| Blob::from(*b)))
-            .rev()
+        let cell_indices = cell_witnesses
+            .iter()
+            .flat_map(|w| w.cell_indices.iter().copied())
+            .collect::<Vec<_>>();
+        let cells = cell_witnesses
+            .iter()
+            .flat_map(|w| w.cells.iter().cloned())
             .collect::<Vec<_>>();
-        Self { entries }
+        let proofs = cell_witnesses
+            .iter()
+            .flat_map(|w| w.proofs.iter().cloned())
+            .collect::<Vec<_>>();
+
+        let valid = kzg
+            .settings()
+            .verify_cell_kzg_proof_batch(
+                commitments.as_slice(),
+                cell_indices.as_slice(),
+                cells.as_slice(),
+                proofs.as_slice(),
+            )
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+        if !valid {
+            return Err(BlobProviderError::Backend(
+                "witness cell kzg proofs failed batch verification".to_string(),
+            ));
+        }
+
+        cell_witnesses
+            .iter()
+            .map(|w| {
+                let blob = Self::blob_from_cell_witness(kzg, w)?;
+                Ok((kzg_to_versioned_hash(w.commitment.as_slice()), blob))
+            })
+            .collect()
+    }
+
+    /// Reconstructs a full blob from a (possibly partial) cell witness, recovering the missing
+    /// cells first when fewer than [CELLS_PER_EXT_BLOB] were provided.
+    ///
+    /// Relies on the EIP-7594 cell layout: cells `0..CELLS_PER_EXT_BLOB / 2` (the first half, in
+    /// cell index order) are the blob's original evaluations, and the second half of
+    /// `CELLS_PER_EXT_BLOB` is the Reed-Solomon extension, so concatenating the first half back together recovers the
+    /// original blob bytes exactly with no further permutation. If that ever turned out not to
+    /// hold for a particular witness, the `blob_to_kzg_commitment` check below still fails closed:
+    /// wrongly-assembled bytes won't derive `witness.commitment`, so this returns `Err` rather
+    /// than silently handing back corrupted data.
+    fn blob_from_cell_witness(
+        kzg: &KzgConfig,
+        witness: &BlobCellWitness,
+    ) -> Result<Blob, BlobProviderError> {
+        let (cells, indices) = if witness.cells.len() == CELLS_PER_EXT_BLOB {
+            // `verify_cell_kzg_proof_batch` checks each `(commitment, index, cell, proof)` triple
+            // independently, so it happily accepts 128 proof-valid cells that all claim the same
+            // index. Without this check that degenerate witness would skip
+            // `recover_cells_and_kzg_proofs` (which rejects a non-covering index set) and the
+            // byte-assembly below would silently leave 127 of the 128 slots zeroed.
+            let mut sorted_indices = witness.cell_indices.clone();
+            sorted_indices.sort_unstable();
+            sorted_indices.dedup();
+            if sorted_indices.len() != CELLS_PER_EXT_BLOB {
+                return Err(BlobProviderError::Backend(
+                    "cell witness claims 128 cells but they do not cover every cell index exactly once".to_string(),
+                ));
+            }
+            (witness.cells.clone(), witness.cell_indices.clone())
+        } else if witness.cells.len() >= MIN_RECOVERABLE_CELLS {
+            let (recovered_cells, _recovered_proofs) = kzg
+                .settings()
+                .recover_cells_and_kzg_proofs(witness.cell_indices.as_slice(), witness.cells.as_slice())
+                .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+            (recovered_cells, (0..CELLS_PER_EXT_BLOB as u64).collect())
+        } else {
+            return Err(BlobProviderError::Backend(format!(
+                "only {} of {} cells available, need at least {MIN_RECOVERABLE_CELLS} to recover the blob",
+                witness.cells.len(),
+                CELLS_PER_EXT_BLOB
+            )));
+        };
+
+        let mut ordered = core::iter::zip(indices, cells).collect::<Vec<_>>();
+        ordered.sort_by_key(|(index, _)| *index);
+
+        let mut extended = vec![0u8; CELLS_PER_EXT_BLOB * BYTES_PER_CELL];
+        for (index, cell) in &ordered {
+            let offset = *index as usize * BYTES_PER_CELL;
+            extended[offset..offset + BYTES_PER_CELL].copy_from_slice(cell.as_ref());
+        }
+
+        let blob_bytes: [u8; FIELD_ELEMENTS_PER_BLOB as usize * 32] = extended
+            [..FIELD_ELEMENTS_PER_BLOB as usize * 32]
+            .try_into()
+            .map_err(|_| BlobProviderError::Backend("cell recovery produced a short blob".to_string()))?;
+        let blob = c_kzg::Blob::new(blob_bytes);
+
+        // Defense-in-depth: don't trust `witness.commitment` as a label for the assembled bytes,
+        // re-derive it from what was actually reconstructed and require it to match.
+        let derived_commitment = kzg
+            .settings()
+            .blob_to_kzg_commitment(&blob)
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+        if derived_commitment != witness.commitment {
+            return Err(BlobProviderError::Backend(
+                "blob reconstructed from cells does not match the witnessed commitment".to_string(),
+            ));
+        }
+
+        Ok(Blob::from(blob))
     }
 }
 
@@ -81,18 +366,231 @@ impl BlobProvider for PreloadedBlobProvider {
 
     async fn get_blobs(
         &mut self,
-        _block_ref: &BlockInfo,
+        block_ref: &BlockInfo,
         blob_hashes: &[IndexedBlobHash],
     ) -> Result<Vec<Box<Blob>>, Self::Error> {
         let blob_count = blob_hashes.len();
         log(&format!("FETCH {blob_count} BLOB(S)"));
         let mut blobs = Vec::with_capacity(blob_count);
         for hash in blob_hashes {
-            let (blob_hash, blob) = self.entries.pop().unwrap();
-            if hash.hash == blob_hash {
-                blobs.push(Box::new(blob));
+            let position = self
+                .entries
+                .iter()
+                .position(|(r, index, versioned_hash, _)| {
+                    r == block_ref && *index == hash.index && *versioned_hash == hash.hash
+                })
+                .ok_or_else(|| {
+                    BlobProviderError::Backend(format!(
+                        "no preloaded blob for block {} index {}",
+                        block_ref.hash, hash.index
+                    ))
+                })?;
+            let (.., blob) = self.entries.remove(position);
+            blobs.push(Box::new(blob));
+        }
+        Ok(blobs)
+    }
+}
+
+/// A beacon-node-backed [BlobProvider] used on the host to drive live derivation. Every blob it
+/// fetches through `get_blobs` or [OnlineBlobProvider::fetch_cell_witness] is also recorded
+/// ([OnlineBlobProvider::fetch_witness] is not), so that [OnlineBlobProvider::take_witness] yields a
+/// [BlobWitnessData] that the zkVM guest can later replay through [PreloadedBlobProvider].
+#[derive(Clone, Debug)]
+pub struct OnlineBlobProvider {
+    client: reqwest::Client,
+    beacon_rpc_url: String,
+    genesis_time: u64,
+    seconds_per_slot: u64,
+    kzg: KzgConfig,
+    witness: Arc<Mutex<BlobWitnessData>>,
+}
+
+#[derive(Deserialize)]
+struct BeaconBlobSidecarResponse {
+    data: Vec<BeaconBlobSidecar>,
+}
+
+#[derive(Deserialize)]
+struct BeaconBlobSidecar {
+    index: String,
+    blob: Bytes,
+    kzg_commitment: Bytes,
+    kzg_proof: Bytes,
+}
+
+impl OnlineBlobProvider {
+    pub fn new(
+        beacon_rpc_url: String,
+        genesis_time: u64,
+        seconds_per_slot: u64,
+        kzg: KzgConfig,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            beacon_rpc_url,
+            genesis_time,
+            seconds_per_slot,
+            kzg,
+            witness: Arc::new(Mutex::new(BlobWitnessData::default())),
+        }
+    }
+
+    /// Returns the [BlobWitnessData] accumulated from every blob fetched so far.
+    pub async fn take_witness(&self) -> BlobWitnessData {
+        self.witness.lock().await.clone()
+    }
+
+    fn slot(&self, block_ref: &BlockInfo) -> Result<u64, BlobProviderError> {
+        block_ref
+            .timestamp
+            .saturating_sub(self.genesis_time)
+            .checked_div(self.seconds_per_slot)
+            .ok_or_else(|| BlobProviderError::Backend("seconds_per_slot must not be zero".to_string()))
+    }
+
+    async fn fetch_sidecars(
+        &self,
+        block_ref: &BlockInfo,
+    ) -> Result<Vec<BeaconBlobSidecar>, BlobProviderError> {
+        let url = format!(
+            "{}/eth/v1/beacon/blob_sidecars/{}",
+            self.beacon_rpc_url.trim_end_matches('/'),
+            self.slot(block_ref)?
+        );
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?
+            .error_for_status()
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?
+            .json::<BeaconBlobSidecarResponse>()
+            .await
+            .map_err(|e| BlobProviderError::Backend(e.to_string()))?;
+        Ok(response.data)
+    }
+
+    /// Returns each distinct `block_ref` among `requests`, in first-seen order, so the caller can
+    /// fetch it exactly once no matter how many requests share it.
+    fn distinct_block_refs(requests: &[BlobFetchRequest]) -> Vec<BlockInfo> {
+        let mut seen = Vec::new();
+        for request in requests {
+            if !seen.contains(&request.block_ref) {
+                seen.push(request.block_ref);
             }
         }
+        seen
+    }
+
+    /// Fetches every blob referenced by `requests` from the beacon node and assembles them into
+    /// a standalone [BlobWitnessData], without touching the live witness accumulator. Requests
+    /// sharing a `block_ref` are served from a single fetch rather than one GET per blob index.
+    pub async fn fetch_witness(
+        &self,
+        requests: &[BlobFetchRequest],
+    ) -> Result<BlobWitnessData, BlobProviderError> {
+        let mut sidecars_by_block = Vec::new();
+        for block_ref in Self::distinct_block_refs(requests) {
+            let sidecars = self.fetch_sidecars(&block_ref).await?;
+            sidecars_by_block.push((block_ref, sidecars));
+        }
+
+        let mut witness = BlobWitnessData::default();
+        for request in requests {
+            let sidecars = &sidecars_by_block
+                .iter()
+                .find(|(block_ref, _)| *block_ref == request.block_ref)
+                .unwrap()
+                .1;
+            let (blob, commitment, proof) = Self::matching_entry(sidecars, &request.blob_hash)?;
+            witness.blobs.push(blob);
+            witness.commitments.push(commitment);
+            witness.proofs.push(proof);
+        }
+        Ok(witness)
+    }
+
+    /// Fetches `request`'s blob and records it in the live witness as a [BlobCellWitness] sampled
+    /// at `sample_indices`, instead of the full-blob proof `get_blobs`/`fetch_witness` record.
+    /// Shrinks the witness for large proposals, at the cost of needing at least half of
+    /// `CELLS_PER_EXT_BLOB` cells present to reconstruct the blob later.
+    pub async fn fetch_cell_witness(
+        &self,
+        request: &BlobFetchRequest,
+        sample_indices: &[u64],
+    ) -> Result<(), BlobProviderError> {
+        let sidecars = self.fetch_sidecars(&request.block_ref).await?;
+        let (blob, commitment, _proof) = Self::matching_entry(&sidecars, &request.blob_hash)?;
+        let cell_witness = BlobCellWitness::sampled(&self.kzg, commitment, &blob, sample_indices)?;
+        self.witness.lock().await.cell_witnesses.push(cell_witness);
+        Ok(())
+    }
+
+    /// Finds the sidecar matching `blob_hash` by index, confirms its commitment derives the
+    /// requested versioned hash, and decodes it into the raw blob/commitment/proof triple.
+    fn matching_entry(
+        sidecars: &[BeaconBlobSidecar],
+        blob_hash: &IndexedBlobHash,
+    ) -> Result<(Blob, Bytes48, Bytes48), BlobProviderError> {
+        let sidecar = sidecars
+            .iter()
+            .find(|s| s.index.parse::<u64>().ok() == Some(blob_hash.index))
+            .ok_or_else(|| {
+                BlobProviderError::Backend(format!(
+                    "missing sidecar for blob index {}",
+                    blob_hash.index
+                ))
+            })?;
+        let blob_bytes: [u8; FIELD_ELEMENTS_PER_BLOB as usize * 32] = sidecar
+            .blob
+            .as_ref()
+            .try_into()
+            .map_err(|_| BlobProviderError::Backend("malformed blob sidecar".to_string()))?;
+        let commitment_bytes: [u8; 48] = sidecar
+            .kzg_commitment
+            .as_ref()
+            .try_into()
+            .map_err(|_| BlobProviderError::Backend("malformed commitment".to_string()))?;
+        let proof_bytes: [u8; 48] = sidecar
+            .kzg_proof
+            .as_ref()
+            .try_into()
+            .map_err(|_| BlobProviderError::Backend("malformed proof".to_string()))?;
+        let commitment = Bytes48::new(commitment_bytes);
+        let versioned_hash = kzg_to_versioned_hash(commitment.as_slice());
+        if versioned_hash != blob_hash.hash {
+            return Err(BlobProviderError::Backend(format!(
+                "sidecar commitment for index {} does not match requested hash",
+                blob_hash.index
+            )));
+        }
+        let blob = Blob::from(c_kzg::Blob::new(blob_bytes));
+        Ok((blob, commitment, Bytes48::new(proof_bytes)))
+    }
+}
+
+#[async_trait]
+impl BlobProvider for OnlineBlobProvider {
+    type Error = BlobProviderError;
+
+    async fn get_blobs(
+        &mut self,
+        block_ref: &BlockInfo,
+        blob_hashes: &[IndexedBlobHash],
+    ) -> Result<Vec<Box<Blob>>, Self::Error> {
+        log(&format!("FETCH {} BLOB(S) FROM BEACON NODE", blob_hashes.len()));
+        let sidecars = self.fetch_sidecars(block_ref).await?;
+        let mut blobs = Vec::with_capacity(blob_hashes.len());
+        let mut witness = self.witness.lock().await;
+        for hash in blob_hashes {
+            let (blob, commitment, proof) = Self::matching_entry(&sidecars, hash)?;
+            witness.blobs.push(blob);
+            witness.commitments.push(commitment);
+            witness.proofs.push(proof);
+            blobs.push(Box::new(blob));
+        }
         Ok(blobs)
     }
 }
@@ -117,6 +615,448 @@ pub fn field_elements(
     Ok(field_elements)
 }
 
+/// Reduces `hash` mod `BLS_MODULUS` to fit it into a single field element. Lossy: the original
+/// hash is not generally recoverable from the result. Prefer [encode_outputs]/[decode_outputs]
+/// for values that must round-trip exactly, such as stored proposal outputs.
 pub fn hash_to_fe(hash: B256) -> U256 {
     U256::from_be_bytes(hash.0).reduce_mod(BLS_MODULUS)
 }
+
+/// Format version for the [encode_outputs]/[decode_outputs] blob codec.
+const OUTPUTS_CODEC_V1: u8 = 1;
+/// Bytes of the keccak checksum kept in the header; enough to catch corruption/truncation
+/// without wasting header space that could hold top-byte table entries instead.
+const OUTPUTS_CHECKSUM_LEN: usize = 8;
+/// A field element holds 32 bytes, but must stay canonically below `BLS_MODULUS`, so only its
+/// low 31 bytes are usable payload once the top byte is reserved/zeroed.
+const PAYLOAD_BYTES_PER_FE: usize = 31;
+
+/// Encodes `outputs` as a self-describing blob: field element 0 is a header carrying a format
+/// version, the element count, and a keccak checksum over the payload; it is followed by a table
+/// of the top byte zeroed out of each output (so every following field element is canonically
+/// below `BLS_MODULUS`) and then the outputs themselves with their top byte zeroed. This lets
+/// [decode_outputs] recover `outputs` exactly without being told the split point out of band, and
+/// detect truncation/corruption via the checksum.
+pub fn encode_outputs(outputs: &[B256]) -> anyhow::Result<Blob> {
+    let count = outputs.len();
+    let table_len = count.div_ceil(PAYLOAD_BYTES_PER_FE);
+    if 1 + table_len + count > FIELD_ELEMENTS_PER_BLOB as usize {
+        anyhow::bail!("{count} output(s) do not fit in a single blob");
+    }
+    let count: u16 = count
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("too many outputs to encode: {}", outputs.len()))?;
+
+    let payload = outputs.iter().flat_map(|o| o.0).collect::<Vec<_>>();
+    let checksum = keccak256(&payload);
+
+    let mut blob = Blob::default();
+    let mut header = [0u8; 32];
+    header[0] = OUTPUTS_CODEC_V1;
+    header[1..3].copy_from_slice(&count.to_be_bytes());
+    header[3..3 + OUTPUTS_CHECKSUM_LEN].copy_from_slice(&checksum[..OUTPUTS_CHECKSUM_LEN]);
+    blob.0[0..32].copy_from_slice(&header);
+
+    let top_bytes = outputs.iter().map(|o| o.0[0]).collect::<Vec<_>>();
+    for (i, chunk) in top_bytes.chunks(PAYLOAD_BYTES_PER_FE).enumerate() {
+        let offset = 32 * (1 + i);
+        blob.0[offset + 1..offset + 1 + chunk.len()].copy_from_slice(chunk);
+    }
+
+    for (i, output) in outputs.iter().enumerate() {
+        let offset = 32 * (1 + table_len + i);
+        blob.0[offset + 1..offset + 32].copy_from_slice(&output.0[1..32]);
+    }
+
+    Ok(blob)
+}
+
+/// Decodes a blob previously produced by [encode_outputs], recovering the original outputs and
+/// rejecting a blob whose header is missing/unrecognized or whose payload fails the checksum.
+pub fn decode_outputs(blob_data: &BlobData) -> anyhow::Result<Vec<B256>> {
+    let header = &blob_data.blob.0[0..32];
+    if header[0] != OUTPUTS_CODEC_V1 {
+        anyhow::bail!("unrecognized outputs codec version: {}", header[0]);
+    }
+    let count = u16::from_be_bytes([header[1], header[2]]) as usize;
+    let checksum = &header[3..3 + OUTPUTS_CHECKSUM_LEN];
+
+    let table_len = count.div_ceil(PAYLOAD_BYTES_PER_FE);
+    if 1 + table_len + count > FIELD_ELEMENTS_PER_BLOB as usize {
+        anyhow::bail!("outputs header claims {count} element(s), which overflows the blob");
+    }
+
+    let mut top_bytes = Vec::with_capacity(count);
+    for i in 0..table_len {
+        let offset = 32 * (1 + i);
+        top_bytes.extend_from_slice(&blob_data.blob.0[offset + 1..offset + 32]);
+    }
+    top_bytes.truncate(count);
+
+    let mut outputs = Vec::with_capacity(count);
+    for i in 0..count {
+        let offset = 32 * (1 + table_len + i);
+        let mut bytes = [0u8; 32];
+        bytes[0] = top_bytes[i];
+        bytes[1..32].copy_from_slice(&blob_data.blob.0[offset + 1..offset + 32]);
+        outputs.push(B256::from(bytes));
+    }
+
+    let payload = outputs.iter().flat_map(|o| o.0).collect::<Vec<_>>();
+    if keccak256(&payload)[..OUTPUTS_CHECKSUM_LEN] != *checksum {
+        anyhow::bail!("outputs payload failed the blob header checksum");
+    }
+
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_blob() -> Blob {
+        let mut bytes = [0u8; FIELD_ELEMENTS_PER_BLOB as usize * 32];
+        bytes[64] = 0x42;
+        bytes[131000] = 0x7;
+        Blob::from(c_kzg::Blob::new(bytes))
+    }
+
+    fn blob_with_byte(byte: u8) -> Blob {
+        let mut bytes = [0u8; FIELD_ELEMENTS_PER_BLOB as usize * 32];
+        bytes[64] = byte;
+        Blob::from(c_kzg::Blob::new(bytes))
+    }
+
+    fn block_info(number: u64) -> BlockInfo {
+        BlockInfo {
+            hash: B256::repeat_byte(number as u8),
+            number,
+            parent_hash: B256::ZERO,
+            timestamp: 0,
+        }
+    }
+
+    /// Computes a real commitment/proof for `blob` and wraps it into the `(Blob, Bytes48,
+    /// Bytes48, B256)` tuple of (blob, commitment, proof, versioned hash) that a genuine witness
+    /// entry is made of.
+    fn witnessed_entry(kzg: &KzgConfig, blob: Blob) -> (Blob, Bytes48, Bytes48, B256) {
+        let c_blob = c_kzg::Blob::new(blob.0);
+        let commitment = kzg.settings().blob_to_kzg_commitment(&c_blob).unwrap();
+        let proof = kzg
+            .settings()
+            .compute_blob_kzg_proof(&c_blob, &commitment)
+            .unwrap();
+        let hash = kzg_to_versioned_hash(commitment.as_slice());
+        (blob, commitment, proof, hash)
+    }
+
+    fn blob_data_of(blob: Blob) -> BlobData {
+        BlobData { blob, ..Default::default() }
+    }
+
+    fn output(seed: u8) -> B256 {
+        let mut bytes = [0u8; 32];
+        bytes[0] = seed;
+        bytes[31] = seed;
+        // Keep the top byte small; the top-byte table carries any byte value, so the codec would
+        // round-trip it unchanged either way.
+        bytes[0] &= 0x0f;
+        B256::from(bytes)
+    }
+
+    #[test]
+    fn outputs_codec_round_trips_empty_input() {
+        let blob = encode_outputs(&[]).unwrap();
+        let decoded = decode_outputs(&blob_data_of(blob)).unwrap();
+        assert_eq!(decoded, Vec::<B256>::new());
+    }
+
+    #[test]
+    fn outputs_codec_round_trips_a_single_output() {
+        let outputs = vec![output(0xab)];
+        let blob = encode_outputs(&outputs).unwrap();
+        let decoded = decode_outputs(&blob_data_of(blob)).unwrap();
+        assert_eq!(decoded, outputs);
+    }
+
+    #[test]
+    fn outputs_codec_round_trips_near_max_capacity() {
+        let mut count = 0usize;
+        while 1 + (count + 1).div_ceil(PAYLOAD_BYTES_PER_FE) + (count + 1)
+            <= FIELD_ELEMENTS_PER_BLOB as usize
+        {
+            count += 1;
+        }
+        let outputs = (0..count).map(|i| output(i as u8)).collect::<Vec<_>>();
+
+        let blob = encode_outputs(&outputs).unwrap();
+        let decoded = decode_outputs(&blob_data_of(blob)).unwrap();
+        assert_eq!(decoded, outputs);
+    }
+
+    #[test]
+    fn encode_outputs_rejects_more_than_fit_in_a_blob() {
+        let mut count = 0usize;
+        while 1 + (count + 1).div_ceil(PAYLOAD_BYTES_PER_FE) + (count + 1)
+            <= FIELD_ELEMENTS_PER_BLOB as usize
+        {
+            count += 1;
+        }
+        let outputs = (0..=count).map(|i| output(i as u8)).collect::<Vec<_>>();
+
+        assert!(encode_outputs(&outputs).is_err());
+    }
+
+    #[test]
+    fn decode_outputs_rejects_a_corrupted_checksum() {
+        let outputs = vec![output(0x11), output(0x22)];
+        let mut blob = encode_outputs(&outputs).unwrap();
+        blob.0[3] ^= 0xff;
+
+        assert!(decode_outputs(&blob_data_of(blob)).is_err());
+    }
+
+    #[test]
+    fn decode_outputs_rejects_an_unrecognized_version() {
+        let outputs = vec![output(0x33)];
+        let mut blob = encode_outputs(&outputs).unwrap();
+        blob.0[0] = 0xee;
+
+        assert!(decode_outputs(&blob_data_of(blob)).is_err());
+    }
+
+    #[test]
+    fn decode_outputs_rejects_an_overflowing_header_count() {
+        let outputs = vec![output(0x44)];
+        let mut blob = encode_outputs(&outputs).unwrap();
+        blob.0[1..3].copy_from_slice(&u16::MAX.to_be_bytes());
+
+        assert!(decode_outputs(&blob_data_of(blob)).is_err());
+    }
+
+    #[test]
+    fn cell_witness_recovers_blob_from_half_its_cells() {
+        let kzg = KzgConfig::default();
+        let blob = sample_blob();
+        let c_blob = c_kzg::Blob::new(blob.0);
+        let commitment = kzg.settings().blob_to_kzg_commitment(&c_blob).unwrap();
+
+        let sample_indices = (0..MIN_RECOVERABLE_CELLS as u64).collect::<Vec<_>>();
+        let witness =
+            BlobCellWitness::sampled(&kzg, commitment, &blob, &sample_indices).unwrap();
+
+        let recovered = PreloadedBlobProvider::blob_from_cell_witness(&kzg, &witness).unwrap();
+        assert_eq!(recovered.0, blob.0);
+    }
+
+    #[test]
+    fn cell_witness_rejects_duplicate_indices_masquerading_as_full_coverage() {
+        let kzg = KzgConfig::default();
+        let blob = sample_blob();
+        let c_blob = c_kzg::Blob::new(blob.0);
+        let commitment = kzg.settings().blob_to_kzg_commitment(&c_blob).unwrap();
+        let (all_cells, all_proofs) = kzg.settings().compute_cells_and_kzg_proofs(&c_blob).unwrap();
+
+        // 128 individually valid (commitment, index 0, cell, proof) triples, all at index 0:
+        // passes a per-triple batch check but does not cover every cell index.
+        let witness = BlobCellWitness {
+            commitment,
+            cell_indices: vec![0; CELLS_PER_EXT_BLOB],
+            cells: vec![all_cells[0]; CELLS_PER_EXT_BLOB],
+            proofs: vec![all_proofs[0]; CELLS_PER_EXT_BLOB],
+        };
+
+        assert!(PreloadedBlobProvider::blob_from_cell_witness(&kzg, &witness).is_err());
+    }
+
+    #[test]
+    fn cell_witness_rejects_a_mislabeled_commitment() {
+        let kzg = KzgConfig::default();
+        let blob = sample_blob();
+        let c_blob = c_kzg::Blob::new(blob.0);
+        let other_commitment = kzg
+            .settings()
+            .blob_to_kzg_commitment(&c_kzg::Blob::new(blob_with_byte(0x09).0))
+            .unwrap();
+        let (all_cells, all_proofs) = kzg.settings().compute_cells_and_kzg_proofs(&c_blob).unwrap();
+
+        // Every cell/proof is genuinely valid for `blob`, but the witness claims a different
+        // blob's commitment.
+        let witness = BlobCellWitness {
+            commitment: other_commitment,
+            cell_indices: (0..CELLS_PER_EXT_BLOB as u64).collect(),
+            cells: all_cells.to_vec(),
+            proofs: all_proofs.to_vec(),
+        };
+
+        assert!(PreloadedBlobProvider::blob_from_cell_witness(&kzg, &witness).is_err());
+    }
+
+    #[test]
+    fn preloaded_blob_provider_binds_blobs_by_hash_regardless_of_order() {
+        let kzg = KzgConfig::default();
+        let (blob_a, commitment_a, proof_a, hash_a) = witnessed_entry(&kzg, blob_with_byte(1));
+        let (blob_b, commitment_b, proof_b, hash_b) = witnessed_entry(&kzg, blob_with_byte(2));
+
+        // Witness carries the blobs in (a, b) order, but `expected` requests them as (b, a) —
+        // binding must go by hash, not position.
+        let witness = BlobWitnessData {
+            blobs: vec![blob_a, blob_b],
+            commitments: vec![commitment_a, commitment_b],
+            proofs: vec![proof_a, proof_b],
+            cell_witnesses: Vec::new(),
+        };
+        let block_ref = block_info(1);
+        let expected = vec![
+            BlobFetchRequest {
+                block_ref,
+                blob_hash: IndexedBlobHash { index: 7, hash: hash_b },
+            },
+            BlobFetchRequest {
+                block_ref,
+                blob_hash: IndexedBlobHash { index: 3, hash: hash_a },
+            },
+        ];
+
+        let provider = PreloadedBlobProvider::new(kzg, witness, &expected).unwrap();
+        assert!(provider
+            .entries
+            .iter()
+            .any(|(r, index, hash, _)| *r == block_ref && *index == 7 && *hash == hash_b));
+        assert!(provider
+            .entries
+            .iter()
+            .any(|(r, index, hash, _)| *r == block_ref && *index == 3 && *hash == hash_a));
+    }
+
+    #[test]
+    fn preloaded_blob_provider_rejects_a_witness_missing_a_requested_hash() {
+        let kzg = KzgConfig::default();
+        let (blob_a, commitment_a, proof_a, _hash_a) = witnessed_entry(&kzg, blob_with_byte(1));
+        let (_blob_b, _commitment_b, _proof_b, hash_b) = witnessed_entry(&kzg, blob_with_byte(2));
+
+        let witness = BlobWitnessData {
+            blobs: vec![blob_a],
+            commitments: vec![commitment_a],
+            proofs: vec![proof_a],
+            cell_witnesses: Vec::new(),
+        };
+        let expected = vec![BlobFetchRequest {
+            block_ref: block_info(1),
+            blob_hash: IndexedBlobHash { index: 0, hash: hash_b },
+        }];
+
+        assert!(PreloadedBlobProvider::new(kzg, witness, &expected).is_err());
+    }
+
+    #[test]
+    fn preloaded_blob_provider_rejects_a_witness_with_an_unrequested_extra_blob() {
+        let kzg = KzgConfig::default();
+        let (blob_a, commitment_a, proof_a, hash_a) = witnessed_entry(&kzg, blob_with_byte(1));
+        let (blob_b, commitment_b, proof_b, _hash_b) = witnessed_entry(&kzg, blob_with_byte(2));
+
+        let witness = BlobWitnessData {
+            blobs: vec![blob_a, blob_b],
+            commitments: vec![commitment_a, commitment_b],
+            proofs: vec![proof_a, proof_b],
+            cell_witnesses: Vec::new(),
+        };
+        let expected = vec![BlobFetchRequest {
+            block_ref: block_info(1),
+            blob_hash: IndexedBlobHash { index: 0, hash: hash_a },
+        }];
+
+        assert!(PreloadedBlobProvider::new(kzg, witness, &expected).is_err());
+    }
+
+    #[test]
+    fn preloaded_blob_provider_rejects_an_invalid_proof_batch() {
+        let kzg = KzgConfig::default();
+        let (blob, commitment, mut proof, hash) = witnessed_entry(&kzg, blob_with_byte(1));
+        proof.as_mut_slice()[0] ^= 0xff;
+
+        let witness = BlobWitnessData {
+            blobs: vec![blob],
+            commitments: vec![commitment],
+            proofs: vec![proof],
+            cell_witnesses: Vec::new(),
+        };
+        let expected = vec![BlobFetchRequest {
+            block_ref: block_info(1),
+            blob_hash: IndexedBlobHash { index: 0, hash },
+        }];
+
+        assert!(PreloadedBlobProvider::new(kzg, witness, &expected).is_err());
+    }
+
+    #[test]
+    fn matching_entry_rejects_a_sidecar_whose_commitment_does_not_derive_the_requested_hash() {
+        let (blob, commitment, proof, _hash) = witnessed_entry(&KzgConfig::default(), blob_with_byte(1));
+        let sidecars = vec![BeaconBlobSidecar {
+            index: "0".to_string(),
+            blob: Bytes::copy_from_slice(&blob.0),
+            kzg_commitment: Bytes::copy_from_slice(commitment.as_slice()),
+            kzg_proof: Bytes::copy_from_slice(proof.as_slice()),
+        }];
+        let wrong_hash = IndexedBlobHash { index: 0, hash: B256::repeat_byte(0xaa) };
+
+        assert!(OnlineBlobProvider::matching_entry(&sidecars, &wrong_hash).is_err());
+    }
+
+    #[test]
+    fn matching_entry_rejects_when_no_sidecar_matches_the_requested_index() {
+        let (blob, commitment, proof, hash) = witnessed_entry(&KzgConfig::default(), blob_with_byte(1));
+        let sidecars = vec![BeaconBlobSidecar {
+            index: "0".to_string(),
+            blob: Bytes::copy_from_slice(&blob.0),
+            kzg_commitment: Bytes::copy_from_slice(commitment.as_slice()),
+            kzg_proof: Bytes::copy_from_slice(proof.as_slice()),
+        }];
+        let requested = IndexedBlobHash { index: 1, hash };
+
+        assert!(OnlineBlobProvider::matching_entry(&sidecars, &requested).is_err());
+    }
+
+    #[test]
+    fn matching_entry_accepts_a_correctly_bound_sidecar() {
+        let (blob, commitment, proof, hash) = witnessed_entry(&KzgConfig::default(), blob_with_byte(1));
+        let sidecars = vec![BeaconBlobSidecar {
+            index: "2".to_string(),
+            blob: Bytes::copy_from_slice(&blob.0),
+            kzg_commitment: Bytes::copy_from_slice(commitment.as_slice()),
+            kzg_proof: Bytes::copy_from_slice(proof.as_slice()),
+        }];
+        let requested = IndexedBlobHash { index: 2, hash };
+
+        let (found_blob, found_commitment, found_proof) =
+            OnlineBlobProvider::matching_entry(&sidecars, &requested).unwrap();
+        assert_eq!(found_blob.0, blob.0);
+        assert_eq!(found_commitment, commitment);
+        assert_eq!(found_proof, proof);
+    }
+
+    #[test]
+    fn distinct_block_refs_yields_each_block_ref_once_in_first_seen_order() {
+        let shared = block_info(1);
+        let other = block_info(2);
+        let requests = vec![
+            BlobFetchRequest { block_ref: shared, blob_hash: IndexedBlobHash { index: 0, hash: B256::ZERO } },
+            BlobFetchRequest { block_ref: shared, blob_hash: IndexedBlobHash { index: 1, hash: B256::ZERO } },
+            BlobFetchRequest { block_ref: other, blob_hash: IndexedBlobHash { index: 0, hash: B256::ZERO } },
+        ];
+
+        assert_eq!(OnlineBlobProvider::distinct_block_refs(&requests), vec![shared, other]);
+    }
+
+    #[test]
+    fn slot_rejects_a_misconfigured_zero_seconds_per_slot() {
+        let provider = OnlineBlobProvider::new(
+            "http://localhost".to_string(),
+            0,
+            0,
+            KzgConfig::default(),
+        );
+
+        assert!(provider.slot(&block_info(1)).is_err());
+    }
+}